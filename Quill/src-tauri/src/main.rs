@@ -3,8 +3,11 @@
 
 use std::fs;
 use std::path::PathBuf;
+use std::sync::mpsc::channel;
 use std::sync::Mutex;
 
+use notify::{RecursiveMode, Watcher};
+use pulldown_cmark::{html, Options, Parser};
 use serde::{Deserialize, Serialize};
 use tauri::{AppHandle, Manager, State};
 use tauri::Emitter;
@@ -33,6 +36,8 @@ struct Settings {
     window_y: Option<i32>,
     #[serde(default)]
     recent_files: Vec<String>,
+    #[serde(default)]
+    workspace_root: Option<String>,
 }
 
 fn default_theme() -> String { "dark".into() }
@@ -52,18 +57,316 @@ impl Default for Settings {
             window_x: None,
             window_y: None,
             recent_files: Vec::new(),
+            workspace_root: None,
+        }
+    }
+}
+
+/// The on-disk user layer: every field is optional, so a hand-edited
+/// `settings.json` only needs to mention the fields it wants to override.
+/// Anything left out (or the whole file, if absent) falls through to
+/// `Settings::default()`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct UserSettings {
+    theme: Option<String>,
+    font_size: Option<u32>,
+    word_wrap: Option<bool>,
+    window_width: Option<u32>,
+    window_height: Option<u32>,
+    window_x: Option<i32>,
+    window_y: Option<i32>,
+    recent_files: Option<Vec<String>>,
+    workspace_root: Option<String>,
+}
+
+impl UserSettings {
+    fn resolve(&self, defaults: &Settings) -> Settings {
+        Settings {
+            theme: self.theme.clone().unwrap_or_else(|| defaults.theme.clone()),
+            font_size: self.font_size.unwrap_or(defaults.font_size),
+            word_wrap: self.word_wrap.unwrap_or(defaults.word_wrap),
+            window_width: self.window_width.unwrap_or(defaults.window_width),
+            window_height: self.window_height.unwrap_or(defaults.window_height),
+            window_x: self.window_x.or(defaults.window_x),
+            window_y: self.window_y.or(defaults.window_y),
+            recent_files: self
+                .recent_files
+                .clone()
+                .unwrap_or_else(|| defaults.recent_files.clone()),
+            workspace_root: self.workspace_root.clone().or_else(|| defaults.workspace_root.clone()),
         }
     }
 }
 
+#[cfg(test)]
+mod user_settings_tests {
+    use super::*;
+
+    #[test]
+    fn unset_fields_fall_through_to_defaults() {
+        let defaults = Settings { theme: "light".into(), font_size: 12, ..Settings::default() };
+        let resolved = UserSettings::default().resolve(&defaults);
+        assert_eq!(resolved.theme, "light");
+        assert_eq!(resolved.font_size, 12);
+    }
+
+    #[test]
+    fn set_fields_override_defaults() {
+        let user = UserSettings { theme: Some("dark".into()), ..UserSettings::default() };
+        let resolved = user.resolve(&Settings::default());
+        assert_eq!(resolved.theme, "dark");
+        assert_eq!(resolved.font_size, Settings::default().font_size);
+    }
+}
+
+// ─── Layered settings store ───────────────────────────────────────────────────
+
+/// Owns the compiled defaults and the on-disk user layer, and resolves the
+/// two into an effective `Settings` on demand. Setters only ever touch the
+/// user layer, so a hand-edited or synced `settings.json` is never clobbered
+/// with values the user never asked to override.
+struct SettingsStore {
+    config_dir: PathBuf,
+    defaults: Settings,
+    user: UserSettings,
+}
+
+impl SettingsStore {
+    fn path(config_dir: &PathBuf) -> PathBuf {
+        config_dir.join("settings.json")
+    }
+
+    fn load(config_dir: PathBuf) -> Self {
+        let user = Self::read_user_layer(&config_dir).unwrap_or_default();
+        Self {
+            config_dir,
+            defaults: Settings::default(),
+            user,
+        }
+    }
+
+    fn read_user_layer(config_dir: &PathBuf) -> Option<UserSettings> {
+        let content = fs::read_to_string(Self::path(config_dir)).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    fn resolved(&self) -> Settings {
+        self.user.resolve(&self.defaults)
+    }
+
+    /// Re-reads the user layer from disk. Returns `true` if it changed.
+    fn reload(&mut self) -> bool {
+        let Some(user) = Self::read_user_layer(&self.config_dir) else {
+            return false;
+        };
+        if serde_json::to_string(&user).ok() == serde_json::to_string(&self.user).ok() {
+            return false;
+        }
+        self.user = user;
+        true
+    }
+
+    /// Writes the user layer to disk atomically: write to a temp file in the
+    /// same directory, then rename over the real path, so a crash or kill
+    /// mid-write can never leave `settings.json` truncated or half-written.
+    fn persist(&self) {
+        let _ = fs::create_dir_all(&self.config_dir);
+        let Ok(json) = serde_json::to_string_pretty(&self.user) else {
+            return;
+        };
+        let path = Self::path(&self.config_dir);
+        let tmp_path = self.config_dir.join("settings.json.tmp");
+        if fs::write(&tmp_path, json).is_ok() {
+            let _ = fs::rename(&tmp_path, &path);
+        }
+    }
+
+    fn set_theme(&mut self, theme: String) {
+        self.user.theme = Some(theme);
+        self.persist();
+    }
+
+    fn set_font_size(&mut self, size: u32) {
+        self.user.font_size = Some(size);
+        self.persist();
+    }
+
+    fn set_word_wrap(&mut self, enabled: bool) {
+        self.user.word_wrap = Some(enabled);
+        self.persist();
+    }
+
+    fn set_window_size(&mut self, width: u32, height: u32) {
+        self.user.window_width = Some(width);
+        self.user.window_height = Some(height);
+        self.persist();
+    }
+
+    fn set_window_position(&mut self, x: i32, y: i32) {
+        self.user.window_x = Some(x);
+        self.user.window_y = Some(y);
+        self.persist();
+    }
+
+    fn set_recent_files(&mut self, recent_files: Vec<String>) {
+        self.user.recent_files = Some(recent_files);
+        self.persist();
+    }
+
+    fn set_workspace_root(&mut self, workspace_root: Option<String>) {
+        self.user.workspace_root = workspace_root;
+        self.persist();
+    }
+}
+
+// ─── Keymap ─────────────────────────────────────────────────────────────────
+
+/// Action names a key chord may bind to, kept in sync with the commands
+/// registered in `invoke_handler` that make sense as keyboard shortcuts.
+const VALID_ACTIONS: &[&str] = &[
+    "new_file",
+    "open_file",
+    "open_folder",
+    "save_file",
+    "save_file_as",
+    "quick_open",
+    "toggle_word_wrap",
+    "reload_current_file",
+    "export_html",
+];
+
+fn default_keymap() -> std::collections::BTreeMap<String, String> {
+    [
+        ("Ctrl+N", "new_file"),
+        ("Ctrl+O", "open_file"),
+        ("Ctrl+Shift+O", "open_folder"),
+        ("Ctrl+S", "save_file"),
+        ("Ctrl+Shift+S", "save_file_as"),
+        ("Ctrl+P", "quick_open"),
+        ("Alt+Z", "toggle_word_wrap"),
+    ]
+    .into_iter()
+    .map(|(chord, action)| (chord.to_string(), action.to_string()))
+    .collect()
+}
+
+/// Owns the built-in default keymap and the on-disk `keymap.json` overrides,
+/// resolving the two the same way `SettingsStore` layers settings: user
+/// bindings for a chord replace the default, and chords the user never
+/// mentions keep their default binding.
+struct KeymapStore {
+    config_dir: PathBuf,
+    defaults: std::collections::BTreeMap<String, String>,
+    user: std::collections::BTreeMap<String, String>,
+}
+
+impl KeymapStore {
+    fn path(config_dir: &PathBuf) -> PathBuf {
+        config_dir.join("keymap.json")
+    }
+
+    fn load(config_dir: PathBuf) -> Self {
+        let user = Self::read_user_layer(&config_dir).unwrap_or_default();
+        Self {
+            config_dir,
+            defaults: default_keymap(),
+            user,
+        }
+    }
+
+    fn read_user_layer(config_dir: &PathBuf) -> Option<std::collections::BTreeMap<String, String>> {
+        let content = fs::read_to_string(Self::path(config_dir)).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    fn resolved(&self) -> std::collections::BTreeMap<String, String> {
+        let mut merged = self.defaults.clone();
+        merged.extend(self.user.clone());
+        merged
+    }
+
+    /// Re-reads `keymap.json` from disk. Returns `true` if it changed.
+    fn reload(&mut self) -> bool {
+        let Some(user) = Self::read_user_layer(&self.config_dir) else {
+            return false;
+        };
+        if user == self.user {
+            return false;
+        }
+        self.user = user;
+        true
+    }
+
+    fn persist(&self) {
+        let _ = fs::create_dir_all(&self.config_dir);
+        let Ok(json) = serde_json::to_string_pretty(&self.user) else {
+            return;
+        };
+        let path = Self::path(&self.config_dir);
+        let tmp_path = self.config_dir.join("keymap.json.tmp");
+        if fs::write(&tmp_path, json).is_ok() {
+            let _ = fs::rename(&tmp_path, &path);
+        }
+    }
+
+    fn set_binding(&mut self, chord: String, action: String) {
+        self.user.insert(chord, action);
+        self.persist();
+    }
+
+    fn reset(&mut self) {
+        self.user.clear();
+        self.persist();
+    }
+}
+
+#[cfg(test)]
+mod keymap_store_tests {
+    use super::*;
+
+    fn store_with_user(user: std::collections::BTreeMap<String, String>) -> KeymapStore {
+        KeymapStore { config_dir: PathBuf::new(), defaults: default_keymap(), user }
+    }
+
+    #[test]
+    fn user_binding_overrides_default_for_same_chord() {
+        let store = store_with_user(
+            [("Ctrl+S".to_string(), "save_file_as".to_string())].into_iter().collect(),
+        );
+        assert_eq!(store.resolved().get("Ctrl+S"), Some(&"save_file_as".to_string()));
+    }
+
+    #[test]
+    fn unmentioned_chords_keep_their_default() {
+        let store = store_with_user(std::collections::BTreeMap::new());
+        assert_eq!(store.resolved(), default_keymap());
+    }
+}
+
 // ─── App State ───────────────────────────────────────────────────────────────
 
 struct AppState {
+    settings_store: SettingsStore,
+    /// The resolved view of `settings_store`, cached so call sites can keep
+    /// reading `state.settings.*` without re-merging layers on every access.
     settings: Settings,
+    keymap_store: KeymapStore,
     current_file: Option<String>,
     modified: bool,
     startup_file: Option<String>,
-    config_dir: PathBuf,
+    /// mtime/length of `current_file` as of the last read or write, used to
+    /// detect edits made by another program.
+    file_stamp: Option<FileStamp>,
+    /// Source encoding of `current_file`, so saves round-trip it.
+    file_encoding: Option<TextEncoding>,
+}
+
+impl AppState {
+    /// Re-resolves `settings` from `settings_store`. Call after any mutation
+    /// to either layer (a setter, or an external-file reload).
+    fn refresh_settings(&mut self) {
+        self.settings = self.settings_store.resolved();
+    }
 }
 
 type SharedState = Mutex<AppState>;
@@ -96,28 +399,6 @@ fn get_config_dir() -> PathBuf {
     PathBuf::from(".quill")
 }
 
-// ─── Settings persistence ─────────────────────────────────────────────────────
-
-fn load_settings(config_dir: &PathBuf) -> Settings {
-    let path = config_dir.join("settings.json");
-    if path.exists() {
-        if let Ok(content) = fs::read_to_string(&path) {
-            if let Ok(s) = serde_json::from_str::<Settings>(&content) {
-                return s;
-            }
-        }
-    }
-    Settings::default()
-}
-
-fn save_settings(config_dir: &PathBuf, settings: &Settings) {
-    let _ = fs::create_dir_all(config_dir);
-    let path = config_dir.join("settings.json");
-    if let Ok(json) = serde_json::to_string_pretty(settings) {
-        let _ = fs::write(&path, json);
-    }
-}
-
 // ─── Helpers ─────────────────────────────────────────────────────────────────
 
 fn update_title(app: &AppHandle, state: &AppState) {
@@ -138,14 +419,19 @@ fn update_title(app: &AppHandle, state: &AppState) {
     }
 }
 
-fn add_recent_file_impl(settings: &mut Settings, path: &str) {
-    settings.recent_files.retain(|p| p != path);
-    settings.recent_files.insert(0, path.to_string());
-    settings.recent_files.truncate(MAX_RECENT_FILES);
+fn add_recent_file_impl(state: &mut AppState, path: &str) {
+    let mut recent_files = state.settings.recent_files.clone();
+    recent_files.retain(|p| p != path);
+    recent_files.insert(0, path.to_string());
+    recent_files.truncate(MAX_RECENT_FILES);
+    state.settings_store.set_recent_files(recent_files);
+    state.refresh_settings();
 }
 
-/// Read a file as text, trying UTF-8 then falling back to latin-1.
-fn read_file(path: &str) -> Result<String, String> {
+/// Read a file as text, detecting its encoding (BOM'd UTF-8/UTF-16, or
+/// plain UTF-8) and falling back to latin-1 for anything else, so a later
+/// save can round-trip the original bytes instead of silently transcoding.
+fn read_file(path: &str) -> Result<(String, TextEncoding), String> {
     let p = std::path::Path::new(path);
     if !p.exists() {
         return Err("File does not exist".into());
@@ -158,9 +444,154 @@ fn read_file(path: &str) -> Result<String, String> {
         ));
     }
     let bytes = fs::read(p).map_err(|e| e.to_string())?;
-    match String::from_utf8(bytes.clone()) {
-        Ok(s) => Ok(s),
-        Err(_) => Ok(bytes.iter().map(|&b| b as char).collect()),
+    let encoding = TextEncoding::detect(&bytes);
+    Ok((encoding.decode(&bytes), encoding))
+}
+
+/// Snapshot of a file's mtime and length, used to notice edits made by
+/// another program between our read/write and the next save or focus.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct FileStamp {
+    modified: std::time::SystemTime,
+    len: u64,
+}
+
+fn stamp_for(path: &str) -> Option<FileStamp> {
+    let meta = fs::metadata(path).ok()?;
+    Some(FileStamp {
+        modified: meta.modified().ok()?,
+        len: meta.len(),
+    })
+}
+
+#[cfg(test)]
+mod stamp_tests {
+    use super::*;
+
+    #[test]
+    fn stamp_changes_when_file_is_rewritten_with_different_length() {
+        let path = std::env::temp_dir().join(format!("quill-stamp-test-{}.txt", std::process::id()));
+        let path_str = path.to_string_lossy().to_string();
+        fs::write(&path, "short").unwrap();
+        let before = stamp_for(&path_str).unwrap();
+        fs::write(&path, "a much longer replacement body").unwrap();
+        let after = stamp_for(&path_str).unwrap();
+        let _ = fs::remove_file(&path);
+        assert_ne!(before, after);
+    }
+}
+
+// ─── Text Encoding ──────────────────────────────────────────────────────────
+
+/// Source text encoding, detected on read and remembered so `save_to_path`
+/// can write the file back out the same way it came in.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum TextEncoding {
+    Utf8,
+    Utf8Bom,
+    Utf16Le,
+    Utf16Be,
+    Latin1,
+}
+
+impl TextEncoding {
+    /// Sniffs a BOM first (UTF-8, UTF-16 LE/BE), then checks whether the
+    /// bytes are valid UTF-8, and falls back to latin-1 otherwise.
+    fn detect(bytes: &[u8]) -> Self {
+        if bytes.starts_with(&[0xEF, 0xBB, 0xBF]) {
+            Self::Utf8Bom
+        } else if bytes.starts_with(&[0xFF, 0xFE]) {
+            Self::Utf16Le
+        } else if bytes.starts_with(&[0xFE, 0xFF]) {
+            Self::Utf16Be
+        } else if std::str::from_utf8(bytes).is_ok() {
+            Self::Utf8
+        } else {
+            Self::Latin1
+        }
+    }
+
+    fn decode(self, bytes: &[u8]) -> String {
+        match self {
+            Self::Utf8 => String::from_utf8_lossy(bytes).into_owned(),
+            Self::Utf8Bom => String::from_utf8_lossy(&bytes[3..]).into_owned(),
+            Self::Utf16Le => Self::decode_utf16(&bytes[2..], u16::from_le_bytes),
+            Self::Utf16Be => Self::decode_utf16(&bytes[2..], u16::from_be_bytes),
+            Self::Latin1 => bytes.iter().map(|&b| b as char).collect(),
+        }
+    }
+
+    fn decode_utf16(bytes: &[u8], from_bytes: fn([u8; 2]) -> u16) -> String {
+        let units: Vec<u16> = bytes
+            .chunks_exact(2)
+            .map(|c| from_bytes([c[0], c[1]]))
+            .collect();
+        String::from_utf16_lossy(&units)
+    }
+
+    /// Re-encodes `content` as this encoding, re-adding the BOM for the
+    /// variants that carry one.
+    fn encode(self, content: &str) -> Vec<u8> {
+        match self {
+            Self::Utf8 => content.as_bytes().to_vec(),
+            Self::Utf8Bom => {
+                let mut out = vec![0xEF, 0xBB, 0xBF];
+                out.extend_from_slice(content.as_bytes());
+                out
+            }
+            Self::Utf16Le => Self::encode_utf16(content, &[0xFF, 0xFE], u16::to_le_bytes),
+            Self::Utf16Be => Self::encode_utf16(content, &[0xFE, 0xFF], u16::to_be_bytes),
+            // Codepoints above U+00FF have no latin-1 byte; substitute '?'
+            // rather than truncating to the low byte (which would silently
+            // corrupt the file, e.g. U+0100 truncating to a NUL byte).
+            Self::Latin1 => content
+                .chars()
+                .map(|c| if (c as u32) <= 0xFF { c as u8 } else { b'?' })
+                .collect(),
+        }
+    }
+
+    fn encode_utf16(content: &str, bom: &[u8], to_bytes: fn(u16) -> [u8; 2]) -> Vec<u8> {
+        let mut out = bom.to_vec();
+        for unit in content.encode_utf16() {
+            out.extend_from_slice(&to_bytes(unit));
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod text_encoding_tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_utf8_utf16_and_bom_variants() {
+        let content = "hello \u{00e9}\u{00e8} world";
+        for encoding in [
+            TextEncoding::Utf8,
+            TextEncoding::Utf8Bom,
+            TextEncoding::Utf16Le,
+            TextEncoding::Utf16Be,
+        ] {
+            let bytes = encoding.encode(content);
+            assert_eq!(TextEncoding::detect(&bytes), encoding);
+            assert_eq!(encoding.decode(&bytes), content);
+        }
+    }
+
+    #[test]
+    fn latin1_substitutes_question_mark_for_out_of_range_codepoints() {
+        // U+0100 is a multiple of 256, so truncating to `u8` would silently
+        // produce a NUL byte instead of visibly mangling the output.
+        let bytes = TextEncoding::Latin1.encode("a\u{0100}b");
+        assert_eq!(bytes, b"a?b");
+    }
+
+    #[test]
+    fn latin1_passes_through_in_range_codepoints() {
+        let bytes = TextEncoding::Latin1.encode("caf\u{00e9}");
+        assert_eq!(bytes, vec![b'c', b'a', b'f', 0xE9]);
     }
 }
 
@@ -171,6 +602,8 @@ fn new_file(app: AppHandle, state: State<'_, SharedState>) -> serde_json::Value
     let mut s = state.lock().unwrap();
     s.current_file = None;
     s.modified = false;
+    s.file_stamp = None;
+    s.file_encoding = None;
     update_title(&app, &s);
     serde_json::json!({ "success": true, "content": "" })
 }
@@ -193,15 +626,20 @@ fn open_file(app: AppHandle, state: State<'_, SharedState>) -> serde_json::Value
 
     let path_str = path.to_string_lossy().to_string();
     match read_file(&path_str) {
-        Ok(content) => {
+        Ok((content, encoding)) => {
             let mut s = state.lock().unwrap();
             s.current_file = Some(path_str.clone());
             s.modified = false;
-            add_recent_file_impl(&mut s.settings, &path_str);
-            let config_dir = s.config_dir.clone();
-            save_settings(&config_dir, &s.settings);
+            s.file_stamp = stamp_for(&path_str);
+            s.file_encoding = Some(encoding);
+            add_recent_file_impl(&mut s, &path_str);
             update_title(&app, &s);
-            serde_json::json!({ "success": true, "content": content, "path": path_str })
+            serde_json::json!({
+                "success": true,
+                "content": content,
+                "path": path_str,
+                "encoding": encoding,
+            })
         }
         Err(e) => serde_json::json!({ "success": false, "error": e }),
     }
@@ -214,22 +652,33 @@ fn open_recent_file(
     state: State<'_, SharedState>,
 ) -> serde_json::Value {
     match read_file(&path) {
-        Ok(content) => {
+        Ok((content, encoding)) => {
             let mut s = state.lock().unwrap();
             s.current_file = Some(path.clone());
             s.modified = false;
-            add_recent_file_impl(&mut s.settings, &path);
-            let config_dir = s.config_dir.clone();
-            save_settings(&config_dir, &s.settings);
+            s.file_stamp = stamp_for(&path);
+            s.file_encoding = Some(encoding);
+            add_recent_file_impl(&mut s, &path);
             update_title(&app, &s);
-            serde_json::json!({ "success": true, "content": content, "path": path })
+            serde_json::json!({
+                "success": true,
+                "content": content,
+                "path": path,
+                "encoding": encoding,
+            })
         }
         Err(e) => {
             if e.contains("does not exist") {
                 let mut s = state.lock().unwrap();
-                s.settings.recent_files.retain(|p| p != &path);
-                let config_dir = s.config_dir.clone();
-                save_settings(&config_dir, &s.settings);
+                let recent_files: Vec<String> = s
+                    .settings
+                    .recent_files
+                    .iter()
+                    .filter(|p| *p != &path)
+                    .cloned()
+                    .collect();
+                s.settings_store.set_recent_files(recent_files);
+                s.refresh_settings();
             }
             serde_json::json!({ "success": false, "error": e })
         }
@@ -239,20 +688,23 @@ fn open_recent_file(
 #[tauri::command]
 fn save_file(
     content: String,
+    force: bool,
+    encoding: Option<TextEncoding>,
     app: AppHandle,
     state: State<'_, SharedState>,
 ) -> serde_json::Value {
     let current = state.lock().unwrap().current_file.clone();
     if let Some(path) = current {
-        save_to_path(&path, &content, &app, &state)
+        save_to_path(&path, &content, force, encoding, &app, &state)
     } else {
-        save_file_as(content, app, state)
+        save_file_as(content, encoding, app, state)
     }
 }
 
 #[tauri::command]
 fn save_file_as(
     content: String,
+    encoding: Option<TextEncoding>,
     app: AppHandle,
     state: State<'_, SharedState>,
 ) -> serde_json::Value {
@@ -273,25 +725,45 @@ fn save_file_as(
     };
 
     let path_str = path.to_string_lossy().to_string();
-    save_to_path(&path_str, &content, &app, &state)
+    // A freshly picked destination has no tracked stamp to conflict with.
+    save_to_path(&path_str, &content, true, encoding, &app, &state)
 }
 
+/// Writes `content` to `path`, re-encoding it as `encoding` if given,
+/// otherwise the file's previously-detected encoding, otherwise UTF-8.
 fn save_to_path(
     path: &str,
     content: &str,
+    force: bool,
+    encoding: Option<TextEncoding>,
     app: &AppHandle,
     state: &State<'_, SharedState>,
 ) -> serde_json::Value {
-    match fs::write(path, content.as_bytes()) {
+    if !force {
+        let s = state.lock().unwrap();
+        let tracking_this_path = s.current_file.as_deref() == Some(path);
+        if let (true, Some(stamp)) = (tracking_this_path, s.file_stamp) {
+            if stamp_for(path).map(|current| current != stamp).unwrap_or(false) {
+                return serde_json::json!({ "success": false, "conflict": true });
+            }
+        }
+    }
+
+    let resolved_encoding = encoding
+        .or_else(|| state.lock().unwrap().file_encoding)
+        .unwrap_or(TextEncoding::Utf8);
+    let bytes = resolved_encoding.encode(content);
+
+    match fs::write(path, &bytes) {
         Ok(_) => {
             let mut s = state.lock().unwrap();
             s.current_file = Some(path.to_string());
             s.modified = false;
-            add_recent_file_impl(&mut s.settings, path);
-            let config_dir = s.config_dir.clone();
-            save_settings(&config_dir, &s.settings);
+            s.file_stamp = stamp_for(path);
+            s.file_encoding = Some(resolved_encoding);
+            add_recent_file_impl(&mut s, path);
             update_title(app, &s);
-            serde_json::json!({ "success": true, "path": path })
+            serde_json::json!({ "success": true, "path": path, "encoding": resolved_encoding })
         }
         Err(e) => serde_json::json!({ "success": false, "error": e.to_string() }),
     }
@@ -306,10 +778,9 @@ fn set_current_file(
     let mut s = state.lock().unwrap();
     s.current_file = path.clone();
     s.modified = false;
+    s.file_stamp = path.as_deref().and_then(stamp_for);
     if let Some(p) = &path {
-        add_recent_file_impl(&mut s.settings, p);
-        let config_dir = s.config_dir.clone();
-        save_settings(&config_dir, &s.settings);
+        add_recent_file_impl(&mut s, p);
     }
     update_title(&app, &s);
     serde_json::json!({ "success": true, "path": path })
@@ -333,6 +804,7 @@ fn get_file_state(state: State<'_, SharedState>) -> serde_json::Value {
         "path": s.current_file,
         "modified": s.modified,
         "filename": filename,
+        "encoding": s.file_encoding,
     })
 }
 
@@ -343,6 +815,611 @@ fn set_modified(modified: bool, app: AppHandle, state: State<'_, SharedState>) {
     update_title(&app, &s);
 }
 
+/// Overrides the remembered encoding for `current_file` without touching
+/// disk; the next save writes it out in this encoding instead.
+#[tauri::command]
+fn set_encoding(encoding: TextEncoding, state: State<'_, SharedState>) -> serde_json::Value {
+    state.lock().unwrap().file_encoding = Some(encoding);
+    serde_json::json!({ "success": true, "encoding": encoding })
+}
+
+/// Re-reads `current_file` and resets its stamp, discarding our in-memory
+/// copy in favor of what's on disk now.
+#[tauri::command]
+fn reload_current_file(app: AppHandle, state: State<'_, SharedState>) -> serde_json::Value {
+    let path = match state.lock().unwrap().current_file.clone() {
+        Some(p) => p,
+        None => return serde_json::json!({ "success": false, "error": "No file is open" }),
+    };
+    match read_file(&path) {
+        Ok((content, encoding)) => {
+            let mut s = state.lock().unwrap();
+            s.file_stamp = stamp_for(&path);
+            s.file_encoding = Some(encoding);
+            s.modified = false;
+            update_title(&app, &s);
+            serde_json::json!({ "success": true, "content": content, "encoding": encoding })
+        }
+        Err(e) => serde_json::json!({ "success": false, "error": e }),
+    }
+}
+
+/// Compares `current_file`'s on-disk stamp against what we last recorded and,
+/// if it has changed underneath us, emits `file-changed-on-disk` so the
+/// frontend can prompt to reload or keep the in-memory copy. Deliberately
+/// leaves `file_stamp` untouched: it's the evidence `save_to_path`'s conflict
+/// check compares against, and only an explicit "take theirs"
+/// (`reload_current_file`) or "take mine" (a successful save) may advance it.
+fn check_external_change(app: &AppHandle) {
+    let state: State<'_, SharedState> = app.state();
+    let s = state.lock().unwrap();
+    let Some(path) = s.current_file.clone() else {
+        return;
+    };
+    let Some(current) = stamp_for(&path) else {
+        return;
+    };
+    let changed = s.file_stamp.map(|stamp| stamp != current).unwrap_or(false);
+    if changed {
+        let _ = app.emit("file-changed-on-disk", serde_json::json!({ "path": path }));
+    }
+}
+
+// ─── Workspace / File Tree ─────────────────────────────────────────────────────
+
+const TREE_FILE_EXTENSIONS: &[&str] = &["md", "markdown", "txt"];
+
+#[derive(Debug, Clone, Serialize)]
+struct TreeNode {
+    name: String,
+    path: String,
+    is_dir: bool,
+    /// `None` means "not expanded yet" (ask `read_dir_tree` for this node's
+    /// `path` to lazily fetch the next level); `Some` is a fully-listed level.
+    children: Option<Vec<TreeNode>>,
+}
+
+fn is_tree_file(path: &std::path::Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .map(|ext| TREE_FILE_EXTENSIONS.iter().any(|allowed| allowed.eq_ignore_ascii_case(ext)))
+        .unwrap_or(false)
+}
+
+/// Builds a tree node for `path`, recursing `depth` levels deep. Directories
+/// always show; files are filtered to `TREE_FILE_EXTENSIONS`. A depth of 0
+/// leaves `children` unset so the frontend can request it lazily.
+fn build_tree(path: &std::path::Path, depth: u32) -> TreeNode {
+    let name = path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    let is_dir = path.is_dir();
+    let children = if is_dir && depth > 0 {
+        Some(read_dir_entries(path, depth - 1))
+    } else {
+        None
+    };
+    TreeNode {
+        name,
+        path: path.to_string_lossy().to_string(),
+        is_dir,
+        children,
+    }
+}
+
+fn read_dir_entries(dir: &std::path::Path, child_depth: u32) -> Vec<TreeNode> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut nodes: Vec<TreeNode> = entries
+        .filter_map(|e| e.ok())
+        .filter_map(|entry| {
+            let p = entry.path();
+            if p.is_dir() {
+                Some(build_tree(&p, child_depth))
+            } else if is_tree_file(&p) {
+                Some(TreeNode {
+                    name: p.file_name().unwrap_or_default().to_string_lossy().to_string(),
+                    path: p.to_string_lossy().to_string(),
+                    is_dir: false,
+                    children: None,
+                })
+            } else {
+                None
+            }
+        })
+        .collect();
+    nodes.sort_by(|a, b| match (a.is_dir, b.is_dir) {
+        (true, false) => std::cmp::Ordering::Less,
+        (false, true) => std::cmp::Ordering::Greater,
+        _ => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+    });
+    nodes
+}
+
+#[cfg(test)]
+mod tree_tests {
+    use super::*;
+
+    fn make_workspace() -> PathBuf {
+        let root = std::env::temp_dir()
+            .join(format!("quill-tree-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("sub")).unwrap();
+        fs::write(root.join("notes.md"), "hi").unwrap();
+        fs::write(root.join("image.png"), "binary").unwrap();
+        fs::write(root.join("sub").join("nested.txt"), "hi").unwrap();
+        root
+    }
+
+    #[test]
+    fn filters_non_tree_extensions_and_sorts_dirs_first() {
+        let root = make_workspace();
+        let tree = build_tree(&root, 1);
+        let names: Vec<&str> = tree
+            .children
+            .as_ref()
+            .unwrap()
+            .iter()
+            .map(|n| n.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["sub", "notes.md"]);
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn depth_zero_leaves_children_unset_for_lazy_expansion() {
+        let root = make_workspace();
+        let tree = build_tree(&root, 0);
+        assert!(tree.children.is_none());
+        let _ = fs::remove_dir_all(&root);
+    }
+}
+
+#[tauri::command]
+fn open_folder(app: AppHandle, state: State<'_, SharedState>) -> serde_json::Value {
+    let picked = app.dialog().file().blocking_pick_folder();
+
+    let path = match picked {
+        Some(fp) => match fp.into_path() {
+            Ok(p) => p,
+            Err(_) => return serde_json::json!({ "success": false, "cancelled": true }),
+        },
+        None => return serde_json::json!({ "success": false, "cancelled": true }),
+    };
+
+    let path_str = path.to_string_lossy().to_string();
+    let tree = build_tree(&path, 1);
+
+    let mut s = state.lock().unwrap();
+    s.settings_store.set_workspace_root(Some(path_str.clone()));
+    s.refresh_settings();
+    let _ = app.emit(
+        "workspace-opened",
+        serde_json::json!({ "root": path_str, "tree": tree }),
+    );
+
+    serde_json::json!({ "success": true, "root": path_str, "tree": tree })
+}
+
+/// Returns the persisted workspace root and its tree, if one was open at
+/// last exit, so the frontend can restore it on launch the same way
+/// `open_folder` restores one the user just picked. `None` if no workspace
+/// was open, or if the remembered root no longer exists.
+#[tauri::command]
+fn get_startup_workspace(state: State<'_, SharedState>) -> Option<serde_json::Value> {
+    let root = state.lock().unwrap().settings.workspace_root.clone()?;
+    let path = std::path::Path::new(&root);
+    if !path.is_dir() {
+        return None;
+    }
+    let tree = build_tree(path, 1);
+    Some(serde_json::json!({ "root": root, "tree": tree }))
+}
+
+/// Returns `path`'s tree `depth` levels deep (at least one level, so a depth
+/// of 0 still lists the immediate children), for the sidebar tree to lazily
+/// expand. Also emits `tree-expanded` so a second
+/// webview (or a future call the frontend didn't initiate) stays in sync.
+#[tauri::command]
+fn read_dir_tree(path: String, depth: u32, app: AppHandle) -> serde_json::Value {
+    let node = build_tree(std::path::Path::new(&path), depth.max(1));
+    let _ = app.emit(
+        "tree-expanded",
+        serde_json::json!({ "path": path, "node": node }),
+    );
+    serde_json::to_value(node).unwrap_or(serde_json::Value::Null)
+}
+
+// ─── Quick Open ─────────────────────────────────────────────────────────────────
+
+const QUICK_OPEN_MAX_CANDIDATES: usize = 2000;
+const QUICK_OPEN_MAX_RESULTS: usize = 20;
+
+#[derive(Debug, Clone, Serialize)]
+struct QuickOpenMatch {
+    path: String,
+    score: i64,
+    /// Half-open `[start, end)` char-index ranges into `path` that matched,
+    /// so the frontend can highlight them without re-running the matcher.
+    ranges: Vec<[usize; 2]>,
+}
+
+fn walk_markdown_files(dir: &std::path::Path, out: &mut Vec<String>, limit: usize) {
+    if out.len() >= limit {
+        return;
+    }
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.filter_map(|e| e.ok()) {
+        if out.len() >= limit {
+            return;
+        }
+        let p = entry.path();
+        if p.is_dir() {
+            walk_markdown_files(&p, out, limit);
+        } else if is_tree_file(&p) {
+            out.push(p.to_string_lossy().to_string());
+        }
+    }
+}
+
+/// Subsequence fuzzy match of `query` against `candidate`: every character
+/// of `query` must appear in `candidate`, in order, case-insensitively.
+/// Consecutive runs, matches right after a path separator or a word
+/// boundary (camelCase / `_` / `-` / space), and matches at the basename
+/// start all score higher; the gap between matched characters and the
+/// number of unmatched leading characters are penalized. Returns `None` if
+/// `query` isn't a subsequence of `candidate`.
+fn fuzzy_match(candidate: &str, query: &str) -> Option<(i64, Vec<[usize; 2]>)> {
+    if query.is_empty() {
+        return Some((0, Vec::new()));
+    }
+
+    let chars: Vec<char> = candidate.chars().collect();
+    let query_chars: Vec<char> = query.chars().collect();
+    let basename_start = chars
+        .iter()
+        .rposition(|&c| c == '/' || c == '\\')
+        .map(|i| i + 1)
+        .unwrap_or(0);
+
+    let mut ranges: Vec<[usize; 2]> = Vec::new();
+    let mut score: i64 = 0;
+    let mut qi = 0usize;
+    let mut prev_match: Option<usize> = None;
+    let mut leading_unmatched = 0usize;
+
+    for (ci, &c) in chars.iter().enumerate() {
+        if qi >= query_chars.len() {
+            break;
+        }
+        if !c.eq_ignore_ascii_case(&query_chars[qi]) {
+            continue;
+        }
+
+        let is_word_boundary = ci == 0
+            || matches!(chars[ci - 1], '/' | '\\' | '_' | '-' | ' ' | '.')
+            || (c.is_uppercase() && !chars[ci - 1].is_uppercase());
+        let consecutive = prev_match == Some(ci.wrapping_sub(1)) && ci > 0;
+
+        let mut char_score = 10i64;
+        if consecutive {
+            char_score += 15;
+        }
+        if is_word_boundary {
+            char_score += 10;
+        }
+        if ci == basename_start {
+            char_score += 20;
+        }
+        if c == query_chars[qi] {
+            char_score += 1; // exact-case bonus
+        }
+        match prev_match {
+            Some(prev) => char_score -= (ci - prev - 1) as i64,
+            None => leading_unmatched = ci,
+        }
+        score += char_score;
+
+        match ranges.last_mut() {
+            Some(last) if last[1] == ci => last[1] = ci + 1,
+            _ => ranges.push([ci, ci + 1]),
+        }
+
+        prev_match = Some(ci);
+        qi += 1;
+    }
+
+    if qi < query_chars.len() {
+        return None;
+    }
+    score -= leading_unmatched as i64;
+    Some((score, ranges))
+}
+
+#[cfg(test)]
+mod fuzzy_match_tests {
+    use super::*;
+
+    #[test]
+    fn non_subsequence_does_not_match() {
+        assert!(fuzzy_match("main.rs", "xyz").is_none());
+    }
+
+    #[test]
+    fn basename_start_outranks_mid_path_match() {
+        // "main" matches the basename of both, but only the second one
+        // starts exactly at the basename boundary.
+        let (a, _) = fuzzy_match("src/domain.rs", "main").unwrap();
+        let (b, _) = fuzzy_match("src/main.rs", "main").unwrap();
+        assert!(b > a, "basename-start match ({b}) should outscore mid-word match ({a})");
+    }
+
+    #[test]
+    fn consecutive_run_outranks_scattered_match() {
+        let (consecutive, _) = fuzzy_match("settings.rs", "set").unwrap();
+        let (scattered, _) = fuzzy_match("save_edit_test.rs", "set").unwrap();
+        assert!(
+            consecutive > scattered,
+            "consecutive match ({consecutive}) should outscore scattered match ({scattered})"
+        );
+    }
+
+    #[test]
+    fn reports_matched_char_ranges() {
+        let (_, ranges) = fuzzy_match("main.rs", "main").unwrap();
+        assert_eq!(ranges, vec![[0, 4]]);
+    }
+}
+
+/// Fuzzy-matches `query` against recent files and, if a workspace is open,
+/// every markdown file under it (capped at `QUICK_OPEN_MAX_CANDIDATES` so a
+/// huge folder stays responsive), returning the top-scoring matches.
+#[tauri::command]
+fn quick_open(query: String, state: State<'_, SharedState>) -> Vec<QuickOpenMatch> {
+    let s = state.lock().unwrap();
+
+    let mut candidates = s.settings.recent_files.clone();
+    if let Some(root) = &s.settings.workspace_root {
+        let mut files = Vec::new();
+        walk_markdown_files(std::path::Path::new(root), &mut files, QUICK_OPEN_MAX_CANDIDATES);
+        for f in files {
+            if !candidates.contains(&f) {
+                candidates.push(f);
+            }
+        }
+    }
+    candidates.truncate(QUICK_OPEN_MAX_CANDIDATES);
+    drop(s);
+
+    let mut matches: Vec<QuickOpenMatch> = candidates
+        .into_iter()
+        .filter_map(|path| {
+            let (score, ranges) = fuzzy_match(&path, &query)?;
+            Some(QuickOpenMatch { path, score, ranges })
+        })
+        .collect();
+
+    matches.sort_by(|a, b| b.score.cmp(&a.score));
+    matches.truncate(QUICK_OPEN_MAX_RESULTS);
+    matches
+}
+
+// ─── Markdown Export ────────────────────────────────────────────────────────────
+
+fn theme_css(theme: &str) -> &'static str {
+    if theme == "light" {
+        r#"body { background: #ffffff; color: #1a1a1a; }
+a { color: #0969da; }
+code, pre { background: #f6f8fa; }
+pre { padding: 12px; border-radius: 6px; overflow-x: auto; }
+blockquote { border-left: 4px solid #d0d7de; color: #57606a; margin-left: 0; padding-left: 16px; }
+table, th, td { border: 1px solid #d0d7de; border-collapse: collapse; }
+th, td { padding: 6px 12px; }"#
+    } else {
+        r#"body { background: #1e1e1e; color: #d4d4d4; }
+a { color: #4daafc; }
+code, pre { background: #2d2d2d; }
+pre { padding: 12px; border-radius: 6px; overflow-x: auto; }
+blockquote { border-left: 4px solid #3c3c3c; color: #9a9a9a; margin-left: 0; padding-left: 16px; }
+table, th, td { border: 1px solid #3c3c3c; border-collapse: collapse; }
+th, td { padding: 6px 12px; }"#
+    }
+}
+
+/// Renders markdown to HTML with the GitHub-flavored extensions enabled
+/// (tables, strikethrough, task lists; fenced code blocks are core
+/// CommonMark and need no extra flag).
+fn render_markdown(content: &str) -> String {
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_TABLES);
+    options.insert(Options::ENABLE_STRIKETHROUGH);
+    options.insert(Options::ENABLE_TASKLISTS);
+    options.insert(Options::ENABLE_FOOTNOTES);
+
+    let parser = Parser::new_ext(content, options);
+    let mut html_out = String::new();
+    html::push_html(&mut html_out, parser);
+    html_out
+}
+
+/// Returns the MIME type for a recognized image extension, or `None` for
+/// anything else — callers use `None` to refuse embedding non-image files.
+fn mime_for_image(path: &std::path::Path) -> Option<&'static str> {
+    let ext = path.extension().and_then(|e| e.to_str())?.to_lowercase();
+    Some(match ext.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "svg" => "image/svg+xml",
+        "webp" => "image/webp",
+        _ => return None,
+    })
+}
+
+fn base64_encode(data: &[u8]) -> String {
+    const CHARS: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity((data.len() + 2) / 3 * 4);
+    for chunk in data.chunks(3) {
+        let b0 = chunk[0];
+        let b1 = *chunk.get(1).unwrap_or(&0);
+        let b2 = *chunk.get(2).unwrap_or(&0);
+        out.push(CHARS[(b0 >> 2) as usize] as char);
+        out.push(CHARS[(((b0 & 0x03) << 4) | (b1 >> 4)) as usize] as char);
+        out.push(if chunk.len() > 1 {
+            CHARS[(((b1 & 0x0f) << 2) | (b2 >> 6)) as usize] as char
+        } else {
+            '='
+        });
+        out.push(if chunk.len() > 2 {
+            CHARS[(b2 & 0x3f) as usize] as char
+        } else {
+            '='
+        });
+    }
+    out
+}
+
+/// Rewrites every `<img src="...">` that doesn't already point at an http(s)
+/// or `data:` URL into a base64 data URI, resolved relative to `base_dir`,
+/// so the exported file has no external file dependencies. Images that
+/// can't be read are left pointing at their original `src`.
+fn inline_local_images(html: &str, base_dir: Option<&std::path::Path>) -> String {
+    let Some(base_dir) = base_dir else {
+        return html.to_string();
+    };
+    // Canonicalize once so every candidate path can be checked for
+    // containment without escaping via `..` or an absolute path.
+    let Ok(canonical_base) = base_dir.canonicalize() else {
+        return html.to_string();
+    };
+
+    let mut out = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(idx) = rest.find("src=\"") {
+        let (before, from_marker) = rest.split_at(idx);
+        out.push_str(before);
+        let after_marker = &from_marker[5..];
+        let Some(end) = after_marker.find('"') else {
+            out.push_str(from_marker);
+            rest = "";
+            break;
+        };
+        let src = &after_marker[..end];
+        rest = &after_marker[end + 1..];
+
+        if src.starts_with("http://") || src.starts_with("https://") || src.starts_with("data:") {
+            out.push_str("src=\"");
+            out.push_str(src);
+            out.push('"');
+            continue;
+        }
+
+        // Only a recognized image extension, resolved and canonicalized to
+        // somewhere inside `base_dir`, is eligible for embedding — this
+        // rejects `../`-style traversal, absolute paths like `/etc/passwd`,
+        // and non-image files (which `mime_for_image` already excludes).
+        let embedded = mime_for_image(&base_dir.join(src)).and_then(|mime| {
+            let canonical_resolved = base_dir.join(src).canonicalize().ok()?;
+            if !canonical_resolved.starts_with(&canonical_base) {
+                return None;
+            }
+            let bytes = fs::read(&canonical_resolved).ok()?;
+            Some((mime, bytes))
+        });
+
+        match embedded {
+            Some((mime, bytes)) => {
+                out.push_str("src=\"data:");
+                out.push_str(mime);
+                out.push_str(";base64,");
+                out.push_str(&base64_encode(&bytes));
+                out.push('"');
+            }
+            None => {
+                out.push_str("src=\"");
+                out.push_str(src);
+                out.push('"');
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn wrap_standalone_html(body: &str, theme: &str, font_size: u32) -> String {
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>Exported from Quill</title>
+<style>
+{css}
+body {{ font-size: {font_size}px; font-family: -apple-system, BlinkMacSystemFont, "Segoe UI", sans-serif; line-height: 1.6; max-width: 860px; margin: 40px auto; padding: 0 20px; }}
+</style>
+</head>
+<body>
+{body}
+</body>
+</html>
+"#,
+        css = theme_css(theme),
+    )
+}
+
+/// Renders the current editor content to a standalone, self-contained HTML
+/// file (inlining the active theme's CSS and font size) and writes it via
+/// the save dialog. `embed_images` additionally inlines local images
+/// referenced relative to the open file as base64 data URIs.
+#[tauri::command]
+fn export_html(
+    content: String,
+    embed_images: bool,
+    app: AppHandle,
+    state: State<'_, SharedState>,
+) -> serde_json::Value {
+    let picked = app
+        .dialog()
+        .file()
+        .add_filter("HTML files", &["html"])
+        .set_file_name("export.html")
+        .blocking_save_file();
+
+    let path = match picked {
+        Some(fp) => match fp.into_path() {
+            Ok(p) => p,
+            Err(_) => return serde_json::json!({ "success": false, "cancelled": true }),
+        },
+        None => return serde_json::json!({ "success": false, "cancelled": true }),
+    };
+
+    let mut body_html = render_markdown(&content);
+    let (theme, font_size, base_dir) = {
+        let s = state.lock().unwrap();
+        let base_dir = s
+            .current_file
+            .as_deref()
+            .and_then(|p| std::path::Path::new(p).parent())
+            .map(|p| p.to_path_buf());
+        (s.settings.theme.clone(), s.settings.font_size, base_dir)
+    };
+
+    if embed_images {
+        body_html = inline_local_images(&body_html, base_dir.as_deref());
+    }
+
+    let document = wrap_standalone_html(&body_html, &theme, font_size);
+    match fs::write(&path, document) {
+        Ok(_) => serde_json::json!({ "success": true, "path": path.to_string_lossy() }),
+        Err(e) => serde_json::json!({ "success": false, "error": e.to_string() }),
+    }
+}
+
 // ─── Recent Files ─────────────────────────────────────────────────────────────
 
 #[tauri::command]
@@ -353,17 +1430,14 @@ fn get_recent_files(state: State<'_, SharedState>) -> Vec<String> {
 #[tauri::command]
 fn add_recent_file(path: String, state: State<'_, SharedState>) {
     let mut s = state.lock().unwrap();
-    add_recent_file_impl(&mut s.settings, &path);
-    let config_dir = s.config_dir.clone();
-    save_settings(&config_dir, &s.settings);
+    add_recent_file_impl(&mut s, &path);
 }
 
 #[tauri::command]
 fn clear_recent_files(state: State<'_, SharedState>) {
     let mut s = state.lock().unwrap();
-    s.settings.recent_files.clear();
-    let config_dir = s.config_dir.clone();
-    save_settings(&config_dir, &s.settings);
+    s.settings_store.set_recent_files(Vec::new());
+    s.refresh_settings();
 }
 
 // ─── Settings Commands ────────────────────────────────────────────────────────
@@ -375,47 +1449,80 @@ fn get_settings(state: State<'_, SharedState>) -> serde_json::Value {
         "theme": s.settings.theme,
         "font_size": s.settings.font_size,
         "word_wrap": s.settings.word_wrap,
+        "workspace_root": s.settings.workspace_root,
     })
 }
 
 #[tauri::command]
 fn set_theme(theme: String, state: State<'_, SharedState>) -> serde_json::Value {
     let mut s = state.lock().unwrap();
-    s.settings.theme = theme.clone();
-    let config_dir = s.config_dir.clone();
-    save_settings(&config_dir, &s.settings);
+    s.settings_store.set_theme(theme.clone());
+    s.refresh_settings();
     serde_json::json!({ "success": true, "theme": theme })
 }
 
 #[tauri::command]
 fn set_font_size(size: u32, state: State<'_, SharedState>) -> serde_json::Value {
     let mut s = state.lock().unwrap();
-    s.settings.font_size = size.clamp(8, 32);
-    let font_size = s.settings.font_size;
-    let config_dir = s.config_dir.clone();
-    save_settings(&config_dir, &s.settings);
+    let font_size = size.clamp(8, 32);
+    s.settings_store.set_font_size(font_size);
+    s.refresh_settings();
     serde_json::json!({ "success": true, "font_size": font_size })
 }
 
 #[tauri::command]
 fn set_word_wrap(enabled: bool, state: State<'_, SharedState>) -> serde_json::Value {
     let mut s = state.lock().unwrap();
-    s.settings.word_wrap = enabled;
-    let config_dir = s.config_dir.clone();
-    save_settings(&config_dir, &s.settings);
+    s.settings_store.set_word_wrap(enabled);
+    s.refresh_settings();
     serde_json::json!({ "success": true, "word_wrap": enabled })
 }
 
 #[tauri::command]
 fn toggle_word_wrap(state: State<'_, SharedState>) -> serde_json::Value {
     let mut s = state.lock().unwrap();
-    s.settings.word_wrap = !s.settings.word_wrap;
-    let word_wrap = s.settings.word_wrap;
-    let config_dir = s.config_dir.clone();
-    save_settings(&config_dir, &s.settings);
+    let word_wrap = !s.settings.word_wrap;
+    s.settings_store.set_word_wrap(word_wrap);
+    s.refresh_settings();
     serde_json::json!({ "success": true, "word_wrap": word_wrap })
 }
 
+// ─── Keymap Commands ────────────────────────────────────────────────────────────
+
+#[tauri::command]
+fn get_keymap(state: State<'_, SharedState>) -> std::collections::BTreeMap<String, String> {
+    state.lock().unwrap().keymap_store.resolved()
+}
+
+#[tauri::command]
+fn set_keybinding(
+    chord: String,
+    action: String,
+    app: AppHandle,
+    state: State<'_, SharedState>,
+) -> serde_json::Value {
+    if !VALID_ACTIONS.contains(&action.as_str()) {
+        return serde_json::json!({
+            "success": false,
+            "error": format!("Unknown action \"{}\"", action),
+        });
+    }
+    let mut s = state.lock().unwrap();
+    s.keymap_store.set_binding(chord, action);
+    let keymap = s.keymap_store.resolved();
+    let _ = app.emit("keymap-changed", &keymap);
+    serde_json::json!({ "success": true, "keymap": keymap })
+}
+
+#[tauri::command]
+fn reset_keymap(app: AppHandle, state: State<'_, SharedState>) -> serde_json::Value {
+    let mut s = state.lock().unwrap();
+    s.keymap_store.reset();
+    let keymap = s.keymap_store.resolved();
+    let _ = app.emit("keymap-changed", &keymap);
+    serde_json::json!({ "success": true, "keymap": keymap })
+}
+
 // ─── Window State Commands ────────────────────────────────────────────────────
 
 #[tauri::command]
@@ -427,10 +1534,8 @@ fn get_window_size(state: State<'_, SharedState>) -> serde_json::Value {
 #[tauri::command]
 fn save_window_size(width: u32, height: u32, state: State<'_, SharedState>) {
     let mut s = state.lock().unwrap();
-    s.settings.window_width = width;
-    s.settings.window_height = height;
-    let config_dir = s.config_dir.clone();
-    save_settings(&config_dir, &s.settings);
+    s.settings_store.set_window_size(width, height);
+    s.refresh_settings();
 }
 
 #[tauri::command]
@@ -442,10 +1547,8 @@ fn get_window_position(state: State<'_, SharedState>) -> serde_json::Value {
 #[tauri::command]
 fn save_window_position(x: i32, y: i32, state: State<'_, SharedState>) {
     let mut s = state.lock().unwrap();
-    s.settings.window_x = Some(x);
-    s.settings.window_y = Some(y);
-    let config_dir = s.config_dir.clone();
-    save_settings(&config_dir, &s.settings);
+    s.settings_store.set_window_position(x, y);
+    s.refresh_settings();
 }
 
 // ─── App Lifecycle Commands ───────────────────────────────────────────────────
@@ -459,15 +1562,15 @@ fn get_startup_file(
     let path = startup_path?;
 
     match read_file(&path) {
-        Ok(content) => {
+        Ok((content, encoding)) => {
             let mut s = state.lock().unwrap();
             s.current_file = Some(path.clone());
             s.modified = false;
-            add_recent_file_impl(&mut s.settings, &path);
-            let config_dir = s.config_dir.clone();
-            save_settings(&config_dir, &s.settings);
+            s.file_stamp = stamp_for(&path);
+            s.file_encoding = Some(encoding);
+            add_recent_file_impl(&mut s, &path);
             update_title(&app, &s);
-            Some(serde_json::json!({ "content": content, "path": path }))
+            Some(serde_json::json!({ "content": content, "path": path, "encoding": encoding }))
         }
         Err(_) => None,
     }
@@ -479,18 +1582,63 @@ fn force_close(app: AppHandle, state: State<'_, SharedState>) {
     if let Some(window) = app.get_webview_window("main") {
         if let (Ok(size), Ok(pos)) = (window.outer_size(), window.outer_position()) {
             let mut s = state.lock().unwrap();
-            s.settings.window_width = size.width;
-            s.settings.window_height = size.height;
-            s.settings.window_x = Some(pos.x);
-            s.settings.window_y = Some(pos.y);
-            let config_dir = s.config_dir.clone();
-            save_settings(&config_dir, &s.settings);
+            s.settings_store.set_window_size(size.width, size.height);
+            s.settings_store.set_window_position(pos.x, pos.y);
+            s.refresh_settings();
         }
         // destroy() skips the close-requested event, preventing re-entry
         let _ = window.destroy();
     }
 }
 
+// ─── Config file watcher ───────────────────────────────────────────────────────
+
+/// Watches `config_dir` for external changes to `settings.json` and
+/// `keymap.json` (hand edits, a sync tool dropping in a new copy, ...) and
+/// re-resolves the matching store whenever one moves, so the running app
+/// picks up changes without a restart.
+fn watch_config_dir(app: AppHandle, config_dir: PathBuf) {
+    std::thread::spawn(move || {
+        let (tx, rx) = channel();
+        let mut watcher = match notify::recommended_watcher(move |res| {
+            let _ = tx.send(res);
+        }) {
+            Ok(w) => w,
+            Err(_) => return,
+        };
+        if watcher
+            .watch(&config_dir, RecursiveMode::NonRecursive)
+            .is_err()
+        {
+            return;
+        }
+
+        loop {
+            match rx.recv() {
+                Ok(Ok(event)) => {
+                    let touches = |name: &str| {
+                        event
+                            .paths
+                            .iter()
+                            .any(|p| p.file_name().map(|n| n == name).unwrap_or(false))
+                    };
+                    let state: State<'_, SharedState> = app.state();
+                    let mut s = state.lock().unwrap();
+                    if touches("settings.json") && s.settings_store.reload() {
+                        s.refresh_settings();
+                        let _ = app.emit("settings-changed", s.settings.clone());
+                    }
+                    if touches("keymap.json") && s.keymap_store.reload() {
+                        let _ = app.emit("keymap-changed", s.keymap_store.resolved());
+                    }
+                }
+                Ok(Err(_)) => continue,
+                Err(_) => break,
+            }
+        }
+    });
+}
+
 // ─── Main ─────────────────────────────────────────────────────────────────────
 
 fn main() {
@@ -501,7 +1649,9 @@ fn main() {
 
     let config_dir = get_config_dir();
     let _ = fs::create_dir_all(&config_dir);
-    let settings = load_settings(&config_dir);
+    let settings_store = SettingsStore::load(config_dir.clone());
+    let settings = settings_store.resolved();
+    let keymap_store = KeymapStore::load(config_dir.clone());
 
     // Capture initial window geometry from settings before moving into closure
     let init_width = settings.window_width;
@@ -512,12 +1662,22 @@ fn main() {
     tauri::Builder::default()
         .plugin(tauri_plugin_dialog::init())
         .manage(Mutex::new(AppState {
+            settings_store,
             settings,
+            keymap_store,
             current_file: None,
             modified: false,
             startup_file: startup_file.clone(),
-            config_dir,
+            file_stamp: None,
+            file_encoding: None,
         }))
+        .on_window_event(|window, event| {
+            // Cheapest reasonable check for external edits: whenever the
+            // window regains focus, compare the current file's stamp.
+            if let tauri::WindowEvent::Focused(true) = event {
+                check_external_change(window.app_handle());
+            }
+        })
         .setup(move |app| {
             if let Some(window) = app.get_webview_window("main") {
                 // Restore saved window geometry (logical pixels to match Python behavior)
@@ -531,6 +1691,7 @@ fn main() {
                     ));
                 }
             }
+            watch_config_dir(app.handle().clone(), config_dir.clone());
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
@@ -542,6 +1703,12 @@ fn main() {
             set_current_file,
             get_file_state,
             set_modified,
+            set_encoding,
+            reload_current_file,
+            open_folder,
+            read_dir_tree,
+            quick_open,
+            export_html,
             get_recent_files,
             add_recent_file,
             clear_recent_files,
@@ -550,11 +1717,15 @@ fn main() {
             set_font_size,
             set_word_wrap,
             toggle_word_wrap,
+            get_keymap,
+            set_keybinding,
+            reset_keymap,
             get_window_size,
             save_window_size,
             get_window_position,
             save_window_position,
             get_startup_file,
+            get_startup_workspace,
             force_close,
         ])
         .build(tauri::generate_context!())